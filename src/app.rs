@@ -2,17 +2,26 @@
 
 use crate::{
     app_config::COLOR_CHANNELS,
+    error::AppError,
+    filter,
     threadpool::ThreadPool,
+    tiler::Tiler,
     CONFIG,
 };
 
 use std::{
-    cell::RefCell, 
-    sync::{Arc, Mutex, OnceLock},
+    cell::RefCell,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, OnceLock,
+    },
     thread,
-    time::Duration,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
+use image::RgbaImage;
+
 use pixels::{Pixels, SurfaceTexture};
 
 use rand::{Rng, SeedableRng};
@@ -45,6 +54,47 @@ enum UserEvent{
 /// This proxy will be used to trigger custom events from the render loop to the winit application window.
 static EVENT_LOOP_PROXY: OnceLock<EventLoopProxy<UserEvent>> = OnceLock::new();
 
+/// Set by the preview window to ask the render loop to stop current work; cleared when a new
+/// render pass starts. Queued tile closures check this before doing any work.
+static CANCEL_RENDER: OnceLock<Arc<AtomicBool>> = OnceLock::new();
+
+/// Set by the preview window to ask the render loop to re-queue every tile once current work has
+/// drained; consumed by `render` when it picks the request back up.
+static RESTART_RENDER: OnceLock<Arc<AtomicBool>> = OnceLock::new();
+
+/// Returns the shared cancel-render flag, creating it on first use.
+fn cancel_render_flag() -> &'static Arc<AtomicBool> {
+    CANCEL_RENDER.get_or_init(|| Arc::new(AtomicBool::new(false)))
+}
+
+/// Returns the shared restart-render flag, creating it on first use.
+fn restart_render_flag() -> &'static Arc<AtomicBool> {
+    RESTART_RENDER.get_or_init(|| Arc::new(AtomicBool::new(false)))
+}
+
+/// Pool of reusable tile pixel buffers exchanged between render threads and the event loop so
+/// sending a tile doesn't allocate a fresh `Vec` on every pass.
+static TILE_BUFFER_POOL: OnceLock<Mutex<Vec<Vec<u8>>>> = OnceLock::new();
+
+/// Returns the shared tile buffer pool, creating it on first use.
+fn tile_buffer_pool() -> &'static Mutex<Vec<Vec<u8>>> {
+    TILE_BUFFER_POOL.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+/// Take a tile-sized buffer from the pool, allocating a new one only if the pool is empty.
+fn take_tile_buffer() -> Vec<u8> {
+    tile_buffer_pool()
+        .lock()
+        .unwrap()
+        .pop()
+        .unwrap_or_else(|| vec![0_u8; CONFIG.tiles_pixel_bytes()])
+}
+
+/// Return a tile buffer to the pool so a future tile can reuse its allocation.
+fn return_tile_buffer(buf: Vec<u8>) {
+    tile_buffer_pool().lock().unwrap().push(buf);
+}
+
 /// The winit application.
 struct App {
     /// The preview window.
@@ -58,13 +108,32 @@ struct App {
 
     /// The inner dimensions of the preview window.
     window_inner_size: PhysicalSize<u32>,
+
+    /// The window's current HiDPI scale factor.
+    scale_factor: f64,
+
+    /// The render thread pool, shut down when the preview window's close button is pressed.
+    pool: Arc<Mutex<ThreadPool>>,
 }
 
 impl App {
+    /// Create a new application that will shut down `pool` when the preview window's close button
+    /// is pressed. Exiting with the Escape key does not shut it down.
+    fn new(pool: Arc<Mutex<ThreadPool>>) -> Self {
+        Self {
+            window: None,
+            pixels: None,
+            pixel_size: LogicalSize::new(CONFIG.width.get(), CONFIG.height.get()),
+            window_inner_size: PhysicalSize::new(CONFIG.width.get(), CONFIG.height.get()),
+            scale_factor: 1.0,
+            pool,
+        }
+    }
+
     /// Render the preview image to the window.
-    fn render(&self) -> Result<(), String> {
+    fn render(&self) -> Result<(), AppError> {
         self.pixels.as_ref().map_or(Ok(()), |pixels| pixels.render())
-            .map_err(|err| format!("{}", err))
+            .map_err(|err| AppError::Render(err.to_string()))
     }
 
     /// Resize the preview image.
@@ -75,7 +144,7 @@ impl App {
         &mut self,
         pixel_size: LogicalSize<u32>,
         window_inner_size: PhysicalSize<u32>,
-    ) -> Result<(), String> {
+    ) -> Result<(), AppError> {
         // Render only if the application has initialized and we have pixels and window.
         self.pixels.as_mut().map_or(Ok(()), |pixels| {
             // Resize the pixel surface texture to fit the windows inner dimensions.
@@ -92,36 +161,48 @@ impl App {
                             self.window.as_ref().map(|window| window.request_redraw());
                             Ok(())
                         }
-                        Err(err) => Err(format!("pixels.resize_buffer() failed.\n{}", err)),
+                        Err(err) => Err(AppError::Surface(format!("pixels.resize_buffer() failed.\n{}", err))),
                     }
                 }
-                Err(err) => Err(format!("pixels.resize_surface() failed to resize frame buffer surface.\n{}", err)),
+                Err(err) => Err(AppError::Surface(format!(
+                    "pixels.resize_surface() failed to resize frame buffer surface.\n{}",
+                    err
+                ))),
             }
         })
     }
-}
 
-impl Default for App {
-    /// Returns the "default value" for `App` initialized to the default dimensions.
-    fn default() -> Self {
-        Self {
-            window: None,
-            pixels: None,
-            pixel_size: LogicalSize::new(CONFIG.width.get(), CONFIG.height.get()),
-            window_inner_size: PhysicalSize::new(CONFIG.width.get(), CONFIG.height.get()),
-        }
+    /// Snapshot whatever tiles have landed in the framebuffer so far and save it to disk, either
+    /// to `CONFIG.output` (format guessed from its extension) or a timestamped PNG in the working
+    /// directory.
+    fn save_framebuffer(&self) -> Result<PathBuf, AppError> {
+        let pixels = self.pixels.as_ref().ok_or_else(|| AppError::Save("No pixel buffer to save".to_string()))?;
+
+        let image = RgbaImage::from_raw(CONFIG.width.get(), CONFIG.height.get(), pixels.frame().to_vec())
+            .ok_or_else(|| AppError::Save("Framebuffer size does not match configured dimensions".to_string()))?;
+
+        let path = CONFIG.output.clone().unwrap_or_else(default_render_path);
+
+        image.save(&path).map_err(|err| AppError::Save(err.to_string()))?;
+        Ok(path)
     }
 }
 
-impl ApplicationHandler<UserEvent> for App {
-    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+impl App {
+    /// Create the preview window and its pixel frame buffer. Returns an error instead of
+    /// panicking so `resumed` can log it and exit the event loop cleanly.
+    fn try_resume(&mut self, event_loop: &ActiveEventLoop) -> Result<(), AppError> {
         // Create a new window.
         let window_attributes = Window::default_attributes()
             .with_title("PBRT v3 (Rust)")
             .with_inner_size(self.window_inner_size)
             .with_resizable(true);
 
-        let window = Arc::new(event_loop.create_window(window_attributes).expect("Unable to create window"));
+        let window = Arc::new(
+            event_loop
+                .create_window(window_attributes)
+                .map_err(|err| AppError::Window(err.to_string()))?,
+        );
 
         // Save the inner dimensions of the preview window.
         let window_inner_size = window.inner_size();
@@ -137,11 +218,23 @@ impl ApplicationHandler<UserEvent> for App {
         // Create pixel frame buffer that matches rendered image dimensions that will be used to display it
         // in the window.
         let pixels = Pixels::new(self.pixel_size.width, self.pixel_size.height, surface_texture)
-            .expect("Unable to create pixel frame buffer for window");
+            .map_err(|err| AppError::Surface(err.to_string()))?;
 
+        self.scale_factor = window.scale_factor();
         self.window = Some(Arc::clone(&window));
         self.pixels = Some(pixels);
         self.window_inner_size = window_inner_size;
+
+        Ok(())
+    }
+}
+
+impl ApplicationHandler<UserEvent> for App {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if let Err(err) = self.try_resume(event_loop) {
+            eprintln!("Error initializing preview window: {}", err);
+            event_loop.exit();
+        }
     }
 
     fn window_event(
@@ -153,6 +246,7 @@ impl ApplicationHandler<UserEvent> for App {
         match event {
             WindowEvent::CloseRequested => {
                 println!("The close button was pressed; stopping");
+                self.pool.lock().unwrap().shutdown();
                 event_loop.exit();
             }
 
@@ -177,6 +271,36 @@ impl ApplicationHandler<UserEvent> for App {
                 }
             }
 
+            WindowEvent::ScaleFactorChanged { scale_factor, mut inner_size_writer } => {
+                // Skip the resize dance if the factor didn't actually change (winit can report
+                // the current value again, e.g. on window creation).
+                if scale_factor == self.scale_factor {
+                    return;
+                }
+                let old_scale_factor = self.scale_factor;
+                self.scale_factor = scale_factor;
+
+                // Recompute the physical window size from its previous logical size so a window
+                // the user resized keeps its logical footprint across a DPI change, rather than
+                // snapping back to the configured image dimensions.
+                let new_window_inner_size = PhysicalSize::new(
+                    (self.window_inner_size.width as f64 / old_scale_factor * scale_factor).round() as u32,
+                    (self.window_inner_size.height as f64 / old_scale_factor * scale_factor).round() as u32,
+                );
+
+                if let Err(err) = inner_size_writer.request_inner_size(new_window_inner_size) {
+                    eprintln!("Error requesting inner size for new scale factor {}", err);
+                }
+
+                match self.resize_pixels(self.pixel_size, new_window_inner_size) {
+                    Ok(()) => (),
+                    Err(err) => {
+                        eprintln!("Error resizing window for new scale factor {}", err);
+                        event_loop.exit();
+                    }
+                }
+            }
+
             WindowEvent::KeyboardInput {
                 event:
                     KeyEvent {
@@ -190,6 +314,21 @@ impl ApplicationHandler<UserEvent> for App {
                     println!("Escape key was pressed; stopping");
                     event_loop.exit();
                 }
+                Key::Character(c) if c.as_str().eq_ignore_ascii_case("s") => {
+                    match self.save_framebuffer() {
+                        Ok(path) => println!("Saved framebuffer to {}", path.display()),
+                        Err(err) => eprintln!("Error saving framebuffer: {}", err),
+                    }
+                }
+                Key::Character(c) if c.as_str().eq_ignore_ascii_case("c") => {
+                    println!("Cancel requested; stopping the current render");
+                    cancel_render_flag().store(true, Ordering::SeqCst);
+                }
+                Key::Character(c) if c.as_str().eq_ignore_ascii_case("r") => {
+                    println!("Restart requested; re-queueing all tiles");
+                    cancel_render_flag().store(true, Ordering::SeqCst);
+                    restart_render_flag().store(true, Ordering::SeqCst);
+                }
                 _ => (),
             },
 
@@ -204,13 +343,15 @@ impl ApplicationHandler<UserEvent> for App {
                     copy_tile(tile_idx, &tile_pixels, window_pixels);
                     self.window.as_ref().map(|window| window.request_redraw());
                 });
+                return_tile_buffer(tile_pixels);
             }
         }
     }
 }
 
-/// Run the event loop displaying a window until it is closed or some error occurs.
-pub fn run_event_loop() -> Result<(), EventLoopError> {
+/// Run the event loop displaying a window until it is closed or some error occurs. `pool` is shut
+/// down when the preview window's close button is pressed; the Escape key exits without doing so.
+pub fn run_event_loop(pool: Arc<Mutex<ThreadPool>>) -> Result<(), EventLoopError> {
     eprintln!("Creating event loop");
     let event_loop = EventLoop::<UserEvent>::with_user_event().build().expect("Unable to create event loop");
 
@@ -218,7 +359,7 @@ pub fn run_event_loop() -> Result<(), EventLoopError> {
     EVENT_LOOP_PROXY.get_or_init(|| event_loop.create_proxy());
 
     eprintln!("Running winit app");
-    let mut app = App::default();
+    let mut app = App::new(pool);
     event_loop.run_app(&mut app)
 }
 
@@ -233,13 +374,69 @@ fn send_user_event(event: UserEvent) {
     EVENT_LOOP_PROXY.get().map(|proxy| proxy.send_event(event));
 }
 
-/// Use a threadpool to queue up all the tiles for rendering.
+/// Use a threadpool to queue up all the tiles for rendering, restarting from scratch whenever
+/// the preview window asks for it.
 pub fn render(pool: Arc<Mutex<ThreadPool>>, remaining_tiles: Arc<Mutex<u32>>) {
-    // Queue up the tiles to render.
-    for tile_idx in 0..CONFIG.tiles() {
-        let remaining_tiles = Arc::clone(&remaining_tiles);
+    let cancel = cancel_render_flag();
+    let restart = restart_render_flag();
+
+    // Decode the optional source image once and share it across render threads and restarts.
+    let source = load_source_image();
+
+    loop {
+        cancel.store(false, Ordering::SeqCst);
+        *remaining_tiles.lock().unwrap() = CONFIG.tiles();
+
+        dispatch_tiles(&pool, &remaining_tiles, cancel, &source);
+        println!("Queued up all tiles to render.");
+
+        // Wait for the render to finish, draining pending tiles if a cancel comes in.
+        loop {
+            if *remaining_tiles.lock().unwrap() == 0 {
+                break;
+            }
+
+            if cancel.load(Ordering::SeqCst) {
+                let drained = pool.lock().unwrap().drain() as u32;
+                if drained > 0 {
+                    *remaining_tiles.lock().unwrap() -= drained;
+                }
+            }
+
+            thread::sleep(Duration::from_millis(100));
+        }
+
+        // Sit idle until a restart is requested; this keeps the pool warm instead of exiting.
+        println!("Render finished; waiting for restart.");
+        while !restart.swap(false, Ordering::SeqCst) {
+            thread::sleep(Duration::from_millis(100));
+        }
+
+        println!("Restarting render.");
+    }
+}
+
+/// Hand out every tile in the configured dispatch order to the pool. Each queued closure checks
+/// `cancel` before doing any work so an in-flight cancel/restart request can be honored quickly.
+fn dispatch_tiles(
+    pool: &Arc<Mutex<ThreadPool>>,
+    remaining_tiles: &Arc<Mutex<u32>>,
+    cancel: &Arc<AtomicBool>,
+    source: &Option<Arc<[u8]>>,
+) {
+    let tiler = Tiler::new(CONFIG.tiles_x(), CONFIG.tiles_y(), CONFIG.tile_order);
+
+    while let Some(tile_idx) = tiler.next_tile() {
+        let remaining_tiles = Arc::clone(remaining_tiles);
+        let cancel = Arc::clone(cancel);
+        let source = source.clone();
 
         pool.lock().unwrap().execute(move || {
+            if cancel.load(Ordering::SeqCst) {
+                *remaining_tiles.lock().unwrap() -= 1;
+                return;
+            }
+
             thread_local! {
                 // Allocate pixels for rendering a tile per thread so we don't allocate for each tile.
                 pub static TILE_PIXELS: RefCell<Vec<u8>> = {
@@ -248,41 +445,151 @@ pub fn render(pool: Arc<Mutex<ThreadPool>>, remaining_tiles: Arc<Mutex<u32>>) {
                 };
             }
 
-            TILE_PIXELS.with_borrow_mut(|tile_pixels| {
-                render_tile(tile_idx, tile_pixels);
-                send_user_event(UserEvent::RenderTile { tile_pixels: tile_pixels.to_owned(), tile_idx });
-            });
+            match source {
+                Some(source) => {
+                    TILE_PIXELS.with_borrow_mut(|tile_pixels| {
+                        render_tile_from_source(tile_idx, &source, tile_pixels);
+
+                        let mut buf = take_tile_buffer();
+                        buf.copy_from_slice(tile_pixels);
+                        send_user_event(UserEvent::RenderTile { tile_pixels: buf, tile_idx });
+                    });
+                }
+                None => {
+                    thread_local! {
+                        // Sum of sample contributions per tile pixel channel, reused across tiles on this
+                        // thread so accumulation never allocates on the hot path.
+                        pub static TILE_ACCUM: RefCell<Vec<f32>> = RefCell::new(vec![0.0_f32; CONFIG.tiles_pixel_bytes()]);
+                    }
+
+                    TILE_ACCUM.with_borrow_mut(|accum| {
+                        accum.iter_mut().for_each(|v| *v = 0.0);
+
+                        TILE_PIXELS.with_borrow_mut(|tile_pixels| {
+                            for sample in 0..CONFIG.samples.get() {
+                                if cancel.load(Ordering::SeqCst) {
+                                    break;
+                                }
+                                render_tile_sample(tile_idx, sample, accum, tile_pixels);
+
+                                let mut buf = take_tile_buffer();
+                                buf.copy_from_slice(tile_pixels);
+                                send_user_event(UserEvent::RenderTile { tile_pixels: buf, tile_idx });
+                            }
+                        });
+                    });
+                }
+            }
 
             *remaining_tiles.lock().unwrap() -= 1;
         });
     }
+}
 
-    println!("Queued up all tiles to render.");
+/// Decode `CONFIG.input`, if given, scale/pad it to the configured image dimensions, and return
+/// the resulting RGBA8 buffer shared across render threads. Returns `None` when no input was
+/// given, in which case tiles fall back to the random progressive fill.
+fn load_source_image() -> Option<Arc<[u8]>> {
+    let path = CONFIG.input.as_ref()?;
 
-    // Wait for render to complete and shutdown pool.
-    loop {
-        if *remaining_tiles.lock().unwrap() == 0 {
-            pool.lock().unwrap().shutdown();
-            break;
-        }
+    let decoded = image::open(path)
+        .unwrap_or_else(|err| panic!("Unable to decode input image {path:?}: {err}"));
 
-        thread::sleep(Duration::from_secs(1));
-    }
+    let scaled = decoded.resize(
+        CONFIG.width.get(),
+        CONFIG.height.get(),
+        image::imageops::FilterType::Lanczos3,
+    );
+
+    // Pad the scaled image onto a canvas matching the configured dimensions exactly.
+    let mut canvas = RgbaImage::new(CONFIG.width.get(), CONFIG.height.get());
+    image::imageops::overlay(&mut canvas, &scaled, 0, 0);
+
+    Some(Arc::from(canvas.into_raw()))
 }
 
-/// Render a single tile adding some random load to simulate rendering algorithm.
-fn render_tile(tile_idx: u32, tile_pixels: &mut [u8]) {
+/// Render a tile by sampling the decoded source image and applying the configured filter, then
+/// sleeping for a random 1..max_load_millis milliseconds to simulate load. The sleep always runs;
+/// there is no setting that turns it off.
+fn render_tile_from_source(tile_idx: u32, source: &[u8], tile_pixels: &mut [u8]) {
+    let (x_min, y_min, x_max, y_max) = get_tile_bounds(tile_idx);
+
+    let w = CONFIG.width.get() as usize;
+    let h = CONFIG.height.get() as usize;
+    let ts = CONFIG.tile_size.get() as usize;
+    let tw = (x_max - x_min + 1) as usize;
+    let th = (y_max - y_min + 1) as usize;
+
+    // Widen the gathered region by a 1px halo (clamped to the image edges) so neighbourhood
+    // filters like box blur see pixels from adjacent tiles instead of seaming at tile boundaries.
+    const HALO: usize = 1;
+    let ex_min = (x_min as usize).saturating_sub(HALO);
+    let ey_min = (y_min as usize).saturating_sub(HALO);
+    let ex_max = (x_max as usize + HALO).min(w - 1);
+    let ey_max = (y_max as usize + HALO).min(h - 1);
+    let ew = ex_max - ex_min + 1;
+    let eh = ey_max - ey_min + 1;
+
+    // Gather the haloed source region into a contiguous ew x eh buffer so filters can address
+    // neighbouring pixels without crossing the source image's row stride.
+    let mut src_tile = vec![0_u8; ew * eh * COLOR_CHANNELS];
+    for y in 0..eh {
+        let src_start = ((ey_min + y) * w + ex_min) * COLOR_CHANNELS;
+        let src_end = src_start + ew * COLOR_CHANNELS;
+
+        let dst_start = y * ew * COLOR_CHANNELS;
+        let dst_end = dst_start + ew * COLOR_CHANNELS;
+
+        src_tile[dst_start..dst_end].copy_from_slice(&source[src_start..src_end]);
+    }
+
+    let mut filtered = vec![0_u8; ew * eh * COLOR_CHANNELS];
+    filter::apply(CONFIG.filter, &src_tile, &mut filtered, ew, eh);
+
+    // Crop the filtered halo back down to this tile's own tw x th region, scattering into
+    // tile_pixels at the full tile_size row stride, which is the layout `copy_tile` (and
+    // `render_tile_sample`'s dense fill) expect for edge tiles where tw/th are smaller than
+    // tile_size.
+    let offset_x = x_min as usize - ex_min;
+    let offset_y = y_min as usize - ey_min;
+    for y in 0..th {
+        let src_start = ((offset_y + y) * ew + offset_x) * COLOR_CHANNELS;
+        let src_end = src_start + tw * COLOR_CHANNELS;
+
+        let dst_start = y * ts * COLOR_CHANNELS;
+        let dst_end = dst_start + tw * COLOR_CHANNELS;
+
+        tile_pixels[dst_start..dst_end].copy_from_slice(&filtered[src_start..src_end]);
+    }
+
+    // Random load, a stand-in for real processing cost.
     let mut rng = ChaCha20Rng::seed_from_u64(tile_idx as u64);
+    thread::sleep(Duration::from_millis(
+        rng.gen_range(1..CONFIG.max_load_millis.get()),
+    ));
+}
+
+/// Render a single progressive sample pass for a tile, adding its contribution into `accum` and
+/// writing the running average so far into `tile_pixels`. Also adds some random load to simulate
+/// a rendering algorithm.
+fn render_tile_sample(tile_idx: u32, sample: u32, accum: &mut [f32], tile_pixels: &mut [u8]) {
+    let mut rng = ChaCha20Rng::seed_from_u64(((tile_idx as u64) << 32) | sample as u64);
 
     let r: u8 = rng.gen_range(0..255);
     let g: u8 = rng.gen_range(0..255);
     let b: u8 = rng.gen_range(0..255);
-
-    for pixel in tile_pixels.chunks_mut(COLOR_CHANNELS) {
-        pixel[0] = r;
-        pixel[1] = g;
-        pixel[2] = b;
-        pixel[3] = 255;
+    let sample_count = (sample + 1) as f32;
+
+    for (pixel, channels) in tile_pixels.chunks_mut(COLOR_CHANNELS).zip(accum.chunks_mut(COLOR_CHANNELS)) {
+        channels[0] += r as f32;
+        channels[1] += g as f32;
+        channels[2] += b as f32;
+        channels[3] += 255.0;
+
+        pixel[0] = (channels[0] / sample_count) as u8;
+        pixel[1] = (channels[1] / sample_count) as u8;
+        pixel[2] = (channels[2] / sample_count) as u8;
+        pixel[3] = (channels[3] / sample_count) as u8;
     }
 
     // Random load.
@@ -334,3 +641,21 @@ fn get_tile_bounds(tile_idx: u32) -> (u32, u32, u32, u32) {
     (x_min, y_min, x_max, y_max)
 }
 
+/// Picks a `render_{timestamp}.png` path in the working directory, appending a numeric suffix if
+/// a file from the same millisecond already exists so pressing the save key twice in quick
+/// succession doesn't silently overwrite the previous snapshot.
+fn default_render_path() -> PathBuf {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+
+    let mut path = PathBuf::from(format!("render_{timestamp}.png"));
+    let mut suffix = 1;
+    while path.exists() {
+        path = PathBuf::from(format!("render_{timestamp}_{suffix}.png"));
+        suffix += 1;
+    }
+    path
+}
+
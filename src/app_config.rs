@@ -4,9 +4,13 @@ use clap::Parser;
 
 use std::{
     num::{NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize},
+    path::PathBuf,
     thread,
 };
 
+use crate::filter::Filter;
+use crate::tiler::TileOrder;
+
 pub const COLOR_CHANNELS: usize = 4;
 
 /// Program configuration.
@@ -58,6 +62,51 @@ pub struct AppConfig {
         help = "max time in milliseconds to use to simulate tile rendering load (default = 100)",
     )]
     pub max_load_millis: NonZeroU64,
+
+    /// Number of progressive samples per tile.
+    #[arg(
+        long = "samples",
+        value_name = "SAMPLES",
+        default_value_t = NonZeroU32::new(1).unwrap(),
+        help = "number of progressive samples to accumulate per tile (default = 1)",
+    )]
+    pub samples: NonZeroU32,
+
+    /// Tile dispatch order.
+    #[arg(
+        long = "tile-order",
+        value_name = "TILE_ORDER",
+        value_enum,
+        default_value_t = TileOrder::Linear,
+        help = "order in which tiles are dispatched for rendering (default = linear)",
+    )]
+    pub tile_order: TileOrder,
+
+    /// Optional source image to render instead of a random fill.
+    #[arg(
+        long = "input",
+        value_name = "PATH",
+        help = "path to an input image to load and process instead of a random fill",
+    )]
+    pub input: Option<PathBuf>,
+
+    /// Per-tile filter applied to the source image.
+    #[arg(
+        long = "filter",
+        value_name = "FILTER",
+        value_enum,
+        default_value_t = Filter::Passthrough,
+        help = "filter to apply to the source image when --input is given (default = passthrough)",
+    )]
+    pub filter: Filter,
+
+    /// Optional path to save the framebuffer to when the save key is pressed.
+    #[arg(
+        long = "output",
+        value_name = "PATH",
+        help = "path to save the framebuffer to when the save key is pressed (format guessed from extension, default = timestamped PNG)",
+    )]
+    pub output: Option<PathBuf>,
 }
 
 impl AppConfig {
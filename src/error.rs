@@ -0,0 +1,32 @@
+//! Application error types
+
+use std::fmt;
+
+/// Errors that can occur while creating or driving the preview window.
+#[derive(Debug)]
+pub enum AppError {
+    /// Failed to create the window.
+    Window(String),
+
+    /// Failed to create or resize the pixel surface/texture.
+    Surface(String),
+
+    /// Failed while rendering a frame.
+    Render(String),
+
+    /// Failed to save the framebuffer to disk.
+    Save(String),
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::Window(err) => write!(f, "window error: {}", err),
+            AppError::Surface(err) => write!(f, "surface error: {}", err),
+            AppError::Render(err) => write!(f, "render error: {}", err),
+            AppError::Save(err) => write!(f, "save error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
@@ -0,0 +1,131 @@
+//! Per-tile filter operations applied to a loaded source image.
+
+use clap::ValueEnum;
+
+use crate::app_config::COLOR_CHANNELS;
+
+/// Selectable per-tile filter operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Filter {
+    /// Copy the source pixels unchanged.
+    Passthrough,
+
+    /// Convert to grayscale using luminance weights.
+    Grayscale,
+
+    /// Average each pixel with its 3x3 neighbourhood.
+    BoxBlur,
+
+    /// Invert each color channel.
+    Invert,
+}
+
+impl std::fmt::Display for Filter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.to_possible_value().unwrap().get_name().fmt(f)
+    }
+}
+
+/// Apply `filter` to the `tile_w` x `tile_h` RGBA8 region in `src`, writing the result into `dst`.
+pub fn apply(filter: Filter, src: &[u8], dst: &mut [u8], tile_w: usize, tile_h: usize) {
+    match filter {
+        Filter::Passthrough => dst.copy_from_slice(src),
+        Filter::Grayscale => apply_grayscale(src, dst),
+        Filter::BoxBlur => apply_box_blur(src, dst, tile_w, tile_h),
+        Filter::Invert => apply_invert(src, dst),
+    }
+}
+
+/// Convert every pixel to grayscale using Rec. 601 luma weights.
+fn apply_grayscale(src: &[u8], dst: &mut [u8]) {
+    for (s, d) in src.chunks(COLOR_CHANNELS).zip(dst.chunks_mut(COLOR_CHANNELS)) {
+        let luma = (0.299 * s[0] as f32 + 0.587 * s[1] as f32 + 0.114 * s[2] as f32) as u8;
+        d[0] = luma;
+        d[1] = luma;
+        d[2] = luma;
+        d[3] = s[3];
+    }
+}
+
+/// Invert the RGB channels of every pixel, leaving alpha untouched.
+fn apply_invert(src: &[u8], dst: &mut [u8]) {
+    for (s, d) in src.chunks(COLOR_CHANNELS).zip(dst.chunks_mut(COLOR_CHANNELS)) {
+        d[0] = 255 - s[0];
+        d[1] = 255 - s[1];
+        d[2] = 255 - s[2];
+        d[3] = s[3];
+    }
+}
+
+/// Average each pixel with its 3x3 neighbourhood, clamped at the tile edges.
+fn apply_box_blur(src: &[u8], dst: &mut [u8], w: usize, h: usize) {
+    for y in 0..h {
+        for x in 0..w {
+            let mut sum = [0_u32; COLOR_CHANNELS];
+            let mut count = 0_u32;
+
+            for ny in y.saturating_sub(1)..=(y + 1).min(h - 1) {
+                for nx in x.saturating_sub(1)..=(x + 1).min(w - 1) {
+                    let idx = (ny * w + nx) * COLOR_CHANNELS;
+                    for (c, total) in sum.iter_mut().enumerate() {
+                        *total += src[idx + c] as u32;
+                    }
+                    count += 1;
+                }
+            }
+
+            let idx = (y * w + x) * COLOR_CHANNELS;
+            for c in 0..COLOR_CHANNELS {
+                dst[idx + c] = (sum[c] / count) as u8;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn passthrough_copies_pixels_unchanged() {
+        let src = [10, 20, 30, 255, 40, 50, 60, 128];
+        let mut dst = [0_u8; 8];
+        apply(Filter::Passthrough, &src, &mut dst, 2, 1);
+        assert_eq!(dst, src);
+    }
+
+    #[test]
+    fn grayscale_uses_rec601_luma_weights() {
+        let src = [255, 0, 0, 255]; // Pure red.
+        let mut dst = [0_u8; 4];
+        apply(Filter::Grayscale, &src, &mut dst, 1, 1);
+        assert_eq!(dst, [76, 76, 76, 255]);
+    }
+
+    #[test]
+    fn invert_flips_color_channels_but_not_alpha() {
+        let src = [10, 20, 30, 255];
+        let mut dst = [0_u8; 4];
+        apply(Filter::Invert, &src, &mut dst, 1, 1);
+        assert_eq!(dst, [245, 235, 225, 255]);
+    }
+
+    #[test]
+    fn box_blur_averages_the_full_neighbourhood_on_a_2x2_tile() {
+        // Every pixel in a 2x2 tile sees all four pixels within its clamped 3x3 neighbourhood.
+        #[rustfmt::skip]
+        let src = [
+            0,   0, 0, 0,
+            100, 0, 0, 0,
+            200, 0, 0, 0,
+            255, 0, 0, 0,
+        ];
+        let mut dst = [0_u8; 16];
+        apply(Filter::BoxBlur, &src, &mut dst, 2, 2);
+
+        let expected_r = ((0 + 100 + 200 + 255) / 4) as u8;
+        for pixel in dst.chunks(COLOR_CHANNELS) {
+            assert_eq!(pixel[0], expected_r);
+        }
+    }
+}
@@ -15,6 +15,13 @@ use app_config::*;
 
 mod threadpool;
 use threadpool::*;
+
+mod tiler;
+
+mod filter;
+
+mod error;
+
 use winit::error::EventLoopError;
 
 static CONFIG: LazyLock<AppConfig> = LazyLock::new(|| AppConfig::parse());
@@ -27,7 +34,14 @@ fn main() -> Result<(), EventLoopError> {
     env_logger::init();
 
     // Create a thread pool for rendering tiles in parallel.
-    let pool = Arc::new(Mutex::new(ThreadPool::build(CONFIG.threads()).unwrap()));
+    let pool = match ThreadPool::build(CONFIG.threads()) {
+        Ok(pool) => pool,
+        Err(err) => {
+            eprintln!("Error creating thread pool: {}", err);
+            std::process::exit(1);
+        }
+    };
+    let pool = Arc::new(Mutex::new(pool));
 
     // Track remaining tiles. It will be used to shutdown the thread pool.
     let remaining_tiles = Arc::new(Mutex::new(CONFIG.tiles()));
@@ -38,6 +52,6 @@ fn main() -> Result<(), EventLoopError> {
         thread::spawn(|| render(pool, remaining_tiles));
     }
 
-    // Run the event loop.
-    run_event_loop()
+    // Run the event loop, shutting down the thread pool when the preview window's close button is pressed.
+    run_event_loop(pool)
 }
@@ -0,0 +1,128 @@
+//! A fixed-size pool of worker threads that execute queued jobs.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// Error returned when a thread pool could not be built.
+#[derive(Debug)]
+pub struct PoolCreationError(String);
+
+impl fmt::Display for PoolCreationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for PoolCreationError {}
+
+/// State shared between the pool and its workers.
+struct Shared {
+    /// Jobs waiting to be picked up by a worker.
+    queue: Mutex<VecDeque<Job>>,
+
+    /// Notified whenever a job is queued or the pool is shut down.
+    condvar: Condvar,
+
+    /// Set once shutdown has been requested so idle workers know to stop waiting.
+    shutdown: AtomicBool,
+}
+
+/// A fixed-size pool of worker threads that execute queued jobs.
+pub struct ThreadPool {
+    shared: Arc<Shared>,
+    workers: Vec<Worker>,
+}
+
+impl ThreadPool {
+    /// Build a pool with `size` worker threads. Returns an error if `size` is zero.
+    pub fn build(size: usize) -> Result<ThreadPool, PoolCreationError> {
+        if size == 0 {
+            return Err(PoolCreationError(
+                "Thread pool size must be greater than zero".to_string(),
+            ));
+        }
+
+        let shared = Arc::new(Shared {
+            queue: Mutex::new(VecDeque::new()),
+            condvar: Condvar::new(),
+            shutdown: AtomicBool::new(false),
+        });
+
+        let workers = (0..size).map(|id| Worker::new(id, Arc::clone(&shared))).collect();
+
+        Ok(ThreadPool { shared, workers })
+    }
+
+    /// Queue a job for execution on the next available worker thread.
+    pub fn execute<F>(&self, f: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.shared.queue.lock().unwrap().push_back(Box::new(f));
+        self.shared.condvar.notify_one();
+    }
+
+    /// Discard every job still waiting to run (jobs already picked up by a worker are
+    /// unaffected) and return how many jobs were discarded.
+    pub fn drain(&self) -> usize {
+        let mut queue = self.shared.queue.lock().unwrap();
+        let drained = queue.len();
+        queue.clear();
+        drained
+    }
+
+    /// Tell the workers to stop, drain anything still queued, and join every worker thread. Jobs
+    /// passed to `execute` afterwards are still queued but never run.
+    pub fn shutdown(&mut self) {
+        self.shared.shutdown.store(true, Ordering::SeqCst);
+        self.shared.queue.lock().unwrap().clear();
+        self.shared.condvar.notify_all();
+
+        for worker in &mut self.workers {
+            if let Some(thread) = worker.thread.take() {
+                thread.join().expect("Worker thread panicked");
+            }
+        }
+    }
+}
+
+/// A single worker thread pulling jobs off the shared queue.
+struct Worker {
+    #[allow(dead_code)]
+    id: usize,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl Worker {
+    fn new(id: usize, shared: Arc<Shared>) -> Worker {
+        let thread = thread::spawn(move || loop {
+            let job = {
+                let mut queue = shared.queue.lock().unwrap();
+                loop {
+                    if let Some(job) = queue.pop_front() {
+                        break Some(job);
+                    }
+                    if shared.shutdown.load(Ordering::SeqCst) {
+                        break None;
+                    }
+                    queue = shared.condvar.wait(queue).unwrap();
+                }
+            };
+
+            match job {
+                Some(job) => job(),
+                None => break,
+            }
+        });
+
+        Worker {
+            id,
+            thread: Some(thread),
+        }
+    }
+}
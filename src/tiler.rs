@@ -0,0 +1,239 @@
+//! Tile dispatch ordering
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use clap::ValueEnum;
+
+/// Order in which tiles are handed out for rendering.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum TileOrder {
+    /// Raster order, left-to-right, top-to-bottom.
+    Linear,
+
+    /// Sorted by Chebyshev distance from the grid center, nearest first.
+    Center,
+
+    /// Clockwise ring starting at the center tile, spiralling outward.
+    Spiral,
+
+    /// Hilbert space-filling curve order.
+    Hilbert,
+}
+
+impl std::fmt::Display for TileOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.to_possible_value().unwrap().get_name().fmt(f)
+    }
+}
+
+/// Hands out tile indices for a `tiles_x` x `tiles_y` grid in the configured `TileOrder`.
+pub struct Tiler {
+    /// Precomputed tile indices in dispatch order.
+    order: Vec<u32>,
+
+    /// Index into `order` of the next tile to hand out.
+    cursor: AtomicUsize,
+}
+
+impl Tiler {
+    /// Build a tiler for a `tiles_x` x `tiles_y` grid dispatched in `order`.
+    pub fn new(tiles_x: u32, tiles_y: u32, order: TileOrder) -> Self {
+        let order = match order {
+            TileOrder::Linear => Self::linear_order(tiles_x, tiles_y),
+            TileOrder::Center => Self::center_order(tiles_x, tiles_y),
+            TileOrder::Spiral => Self::spiral_order(tiles_x, tiles_y),
+            TileOrder::Hilbert => Self::hilbert_order(tiles_x, tiles_y),
+        };
+
+        Self {
+            order,
+            cursor: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the next tile index to render, or `None` once every tile has been handed out.
+    pub fn next_tile(&self) -> Option<u32> {
+        let i = self.cursor.fetch_add(1, Ordering::SeqCst);
+        self.order.get(i).copied()
+    }
+
+    /// Raster order, left-to-right, top-to-bottom.
+    fn linear_order(tiles_x: u32, tiles_y: u32) -> Vec<u32> {
+        (0..tiles_x * tiles_y).collect()
+    }
+
+    /// Tiles sorted by Chebyshev distance from the grid center.
+    fn center_order(tiles_x: u32, tiles_y: u32) -> Vec<u32> {
+        let cx = (tiles_x - 1) as f32 / 2.0;
+        let cy = (tiles_y - 1) as f32 / 2.0;
+
+        let dist = |idx: u32| {
+            let x = (idx % tiles_x) as f32;
+            let y = (idx / tiles_x) as f32;
+            (x - cx).abs().max((y - cy).abs())
+        };
+
+        let mut tiles: Vec<u32> = (0..tiles_x * tiles_y).collect();
+        tiles.sort_by(|&a, &b| dist(a).partial_cmp(&dist(b)).unwrap());
+        tiles
+    }
+
+    /// Clockwise ring starting at the center tile, spiralling outward (step counts 1,1,2,2,3,3,...,
+    /// turning 90 degrees after each run).
+    fn spiral_order(tiles_x: u32, tiles_y: u32) -> Vec<u32> {
+        let total = (tiles_x * tiles_y) as usize;
+        let mut order = Vec::with_capacity(total);
+        let mut seen = vec![false; total];
+
+        let mut push = |x: i32, y: i32, order: &mut Vec<u32>, seen: &mut [bool]| {
+            if x >= 0 && y >= 0 && x < tiles_x as i32 && y < tiles_y as i32 {
+                let idx = y as u32 * tiles_x + x as u32;
+                if !seen[idx as usize] {
+                    seen[idx as usize] = true;
+                    order.push(idx);
+                }
+            }
+        };
+
+        let mut x = (tiles_x as i32 - 1) / 2;
+        let mut y = (tiles_y as i32 - 1) / 2;
+        push(x, y, &mut order, &mut seen);
+
+        // Right, down, left, up - a 90 degree clockwise turn after each run.
+        let directions = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+        let mut dir = 0;
+        let mut step_len = 1;
+
+        while order.len() < total {
+            for _ in 0..2 {
+                let (dx, dy) = directions[dir % directions.len()];
+                for _ in 0..step_len {
+                    x += dx;
+                    y += dy;
+                    push(x, y, &mut order, &mut seen);
+                }
+                dir += 1;
+            }
+            step_len += 1;
+        }
+
+        order
+    }
+
+    /// Tiles sorted by their Hilbert-curve index on the next power-of-two grid, skipping
+    /// out-of-range cells.
+    fn hilbert_order(tiles_x: u32, tiles_y: u32) -> Vec<u32> {
+        let n = tiles_x.max(tiles_y).max(1).next_power_of_two();
+
+        let mut tiles: Vec<u32> = (0..tiles_x * tiles_y).collect();
+        tiles.sort_by_key(|&idx| hilbert_index(n, idx % tiles_x, idx / tiles_x));
+        tiles
+    }
+}
+
+/// Maps `(x, y)` on an `n x n` (`n` a power of two) grid to its index along the Hilbert curve.
+fn hilbert_index(n: u32, mut x: u32, mut y: u32) -> u64 {
+    let mut d: u64 = 0;
+    let mut s = n / 2;
+
+    while s > 0 {
+        let rx = u32::from((x & s) > 0);
+        let ry = u32::from((y & s) > 0);
+        d += s as u64 * s as u64 * ((3 * rx) ^ ry) as u64;
+
+        // Rotate/flip the quadrant.
+        if ry == 0 {
+            if rx == 1 {
+                x = n - 1 - x;
+                y = n - 1 - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+
+        s /= 2;
+    }
+
+    d
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Drain a tiler, returning every tile index it handed out in order.
+    fn collect_all(tiler: &Tiler) -> Vec<u32> {
+        let mut order = Vec::new();
+        while let Some(idx) = tiler.next_tile() {
+            order.push(idx);
+        }
+        order
+    }
+
+    fn assert_permutation(mut order: Vec<u32>, total: u32) {
+        order.sort_unstable();
+        assert_eq!(order, (0..total).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn linear_order_is_raster_order() {
+        let tiler = Tiler::new(5, 3, TileOrder::Linear);
+        assert_eq!(collect_all(&tiler), (0..15).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn center_order_visits_every_tile_once() {
+        let tiler = Tiler::new(5, 3, TileOrder::Center);
+        assert_permutation(collect_all(&tiler), 15);
+    }
+
+    #[test]
+    fn center_order_starts_at_the_middle_tile() {
+        // A 5x3 grid's center tile is at (2, 1), i.e. index 1 * 5 + 2 = 7.
+        let tiler = Tiler::new(5, 3, TileOrder::Center);
+        assert_eq!(tiler.next_tile(), Some(7));
+    }
+
+    #[test]
+    fn spiral_order_visits_every_tile_once() {
+        let tiler = Tiler::new(5, 3, TileOrder::Spiral);
+        assert_permutation(collect_all(&tiler), 15);
+    }
+
+    #[test]
+    fn spiral_order_starts_at_the_center_tile() {
+        let tiler = Tiler::new(5, 3, TileOrder::Spiral);
+        assert_eq!(tiler.next_tile(), Some(7));
+    }
+
+    #[test]
+    fn hilbert_order_visits_every_tile_once_on_a_non_square_grid() {
+        let tiler = Tiler::new(5, 3, TileOrder::Hilbert);
+        assert_permutation(collect_all(&tiler), 15);
+    }
+
+    #[test]
+    fn hilbert_order_visits_every_tile_once_on_a_power_of_two_grid() {
+        let tiler = Tiler::new(4, 4, TileOrder::Hilbert);
+        assert_permutation(collect_all(&tiler), 16);
+    }
+
+    #[test]
+    fn hilbert_index_is_a_bijection_on_a_power_of_two_grid() {
+        let n = 4;
+        let mut seen = std::collections::HashSet::new();
+        for y in 0..n {
+            for x in 0..n {
+                assert!(seen.insert(hilbert_index(n, x, y)));
+            }
+        }
+    }
+
+    #[test]
+    fn next_tile_returns_none_once_exhausted() {
+        let tiler = Tiler::new(2, 2, TileOrder::Linear);
+        for _ in 0..4 {
+            assert!(tiler.next_tile().is_some());
+        }
+        assert_eq!(tiler.next_tile(), None);
+    }
+}